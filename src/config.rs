@@ -6,7 +6,7 @@ use nix::sys::signal::{Signal, SIGKILL};
 use nix::sched::CloneFlags;
 use libc::{uid_t, gid_t};
 
-use crate::fakeroot::{FakeRootMount};
+use crate::fakeroot::{FakeRootMknod, FakeRootMount, MountPropagation};
 use crate::idmap::{UidMap, GidMap};
 use crate::namespace::Namespace;
 use crate::stdio::Closing;
@@ -28,6 +28,9 @@ pub struct Config {
     pub fake_root_mounts: Vec<FakeRootMount>,
     pub fake_root_mkdirs: Vec<CString>,
     pub fake_root_touchs: Vec<CString>,
+    pub fake_root_mknods: Vec<FakeRootMknod>,
+    pub fake_root_symlinks: Vec<(CString, CString)>,
+    pub fake_root_propagation: MountPropagation,
 }
 
 impl Default for Config {
@@ -47,6 +50,9 @@ impl Default for Config {
             fake_root_mounts: Vec::new(),
             fake_root_mkdirs: Vec::new(),
             fake_root_touchs: Vec::new(),
+            fake_root_mknods: Vec::new(),
+            fake_root_symlinks: Vec::new(),
+            fake_root_propagation: MountPropagation::Private,
         }
     }
 }
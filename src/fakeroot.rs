@@ -1,19 +1,268 @@
 use crate::ffi_util::ToCString;
 use crate::{Command, Namespace};
 use libc::{
-    MNT_DETACH, MS_BIND, MS_PRIVATE, MS_RDONLY, MS_REC, MS_REMOUNT, O_CLOEXEC, O_CREAT, O_RDONLY,
+    c_ulong, MNT_DETACH, MS_BIND, MS_NODEV, MS_NOEXEC, MS_NOSUID, MS_PRIVATE, MS_RDONLY, MS_REC,
+    MS_RELATIME, MS_REMOUNT, MS_SHARED, MS_SLAVE, MS_UNBINDABLE, O_CLOEXEC, O_CREAT, O_RDONLY,
 };
 use std::ffi::{c_char, c_void, CString};
+use std::io;
 use std::path::Path;
 
+/// The type of device node to create with `Command::fakeroot_mknod`.
+pub enum DeviceKind {
+    Character,
+    Block,
+}
+
+// Requires `bitflags = "2"` as a direct dependency in Cargo.toml (this crate's manifest isn't
+// part of this source tree, so it can't be edited here - add the entry there before relying on
+// this macro, or the crate simply won't build).
+bitflags::bitflags! {
+    /// Per-mount hardening flags for a fakeroot mount point, applied via an `MS_REMOUNT | MS_BIND`
+    /// pass after the root has been pivoted into.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct MountOptions: u32 {
+        const READONLY = 0b00001;
+        const NOSUID   = 0b00010;
+        const NODEV    = 0b00100;
+        const NOEXEC   = 0b01000;
+        const RELATIME = 0b10000;
+    }
+}
+
+impl MountOptions {
+    fn to_libc_flags(self) -> c_ulong {
+        let mut flags = 0;
+        if self.contains(MountOptions::READONLY) {
+            flags |= MS_RDONLY;
+        }
+        if self.contains(MountOptions::NOSUID) {
+            flags |= MS_NOSUID;
+        }
+        if self.contains(MountOptions::NODEV) {
+            flags |= MS_NODEV;
+        }
+        if self.contains(MountOptions::NOEXEC) {
+            flags |= MS_NOEXEC;
+        }
+        if self.contains(MountOptions::RELATIME) {
+            flags |= MS_RELATIME;
+        }
+        flags
+    }
+}
+
+/// Mount propagation mode for the fakeroot's new root, set via `Command::fakeroot_propagation`.
+///
+/// Defaults to `Private`, matching the previous hardcoded `MS_PRIVATE` behavior.
+#[derive(Clone, Copy, Default)]
+pub enum MountPropagation {
+    #[default]
+    Private,
+    Slave,
+    Shared,
+    Unbindable,
+}
+
+impl MountPropagation {
+    fn to_libc_flag(self) -> c_ulong {
+        match self {
+            MountPropagation::Private => MS_PRIVATE,
+            MountPropagation::Slave => MS_SLAVE,
+            MountPropagation::Shared => MS_SHARED,
+            MountPropagation::Unbindable => MS_UNBINDABLE,
+        }
+    }
+}
+
+enum FakeRootMountKind {
+    /// Bind-mount an existing host path.
+    Bind(CString),
+    /// Mount a special filesystem by type, e.g. "proc" or "tmpfs", with an optional mount-data
+    /// options string (e.g. "size=64m,mode=0755,uid=0,gid=0" for tmpfs).
+    Special {
+        fstype: CString,
+        data: Option<CString>,
+    },
+    /// Overlay one or more lowerdirs with a writable upper/work dir pair.
+    Overlay {
+        lowerdirs: Vec<CString>,
+        upperdir: CString,
+        workdir: CString,
+    },
+}
+
 pub struct FakeRootMount {
     mountpoint: CString,
     mountpoint_outer: CString,
-    src: CString,
-    readonly: bool,
-    is_special_fs: bool, // "src" is a filesystem type like "proc" or "tmpfs"
+    options: MountOptions,
+    kind: FakeRootMountKind,
 }
 
+pub struct FakeRootMknod {
+    path: CString,
+    path_outer: CString,
+    kind: DeviceKind,
+    major: u32,
+    minor: u32,
+    mode: u32,
+}
+
+/// A failure while setting up the fakeroot jail, tagged with the path (and `io::Error`) of the
+/// syscall that failed.
+#[derive(Debug)]
+pub enum FakeRootError {
+    /// Setting the propagation mode of the root mount tree (`MS_PRIVATE` by default, `MS_REC` on
+    /// "/") failed.
+    MountPrivate(io::Error),
+    /// Mounting the tmpfs that backs the fakeroot `base` directory failed.
+    CreateTmpfs(io::Error),
+    /// `mkdir()` of a directory inside the fakeroot failed.
+    Mkdir { path: String, source: io::Error },
+    /// A bind mount (regular mount, or the mknod-to-bind-mount fallback) failed.
+    BindMount {
+        src: String,
+        dst: String,
+        source: io::Error,
+    },
+    /// Mounting a special filesystem (e.g. "proc" or "tmpfs") failed.
+    MountSpecial {
+        fstype: String,
+        dst: String,
+        source: io::Error,
+    },
+    /// Mounting an overlayfs failed.
+    MountOverlay { dst: String, source: io::Error },
+    /// `mknod()` of a device node failed for a reason other than `EPERM`.
+    Mknod { path: String, source: io::Error },
+    /// Neither `pivot_root()` nor the classic `chroot()` fallback succeeded.
+    PivotRoot(io::Error),
+    /// Remounting a directory with its `MountOptions` (read-only, nosuid, ...) failed.
+    Remount { path: String, source: io::Error },
+}
+
+impl std::fmt::Display for FakeRootError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FakeRootError::MountPrivate(e) => write!(f, "failed to set mount propagation: {}", e),
+            FakeRootError::CreateTmpfs(e) => write!(f, "failed to create fakeroot tmpfs: {}", e),
+            FakeRootError::Mkdir { path, source } => {
+                write!(f, "failed to mkdir {}: {}", path, source)
+            }
+            FakeRootError::BindMount { src, dst, source } => {
+                write!(f, "failed to bind-mount {} to {}: {}", src, dst, source)
+            }
+            FakeRootError::MountSpecial { fstype, dst, source } => {
+                write!(f, "failed to mount {} at {}: {}", fstype, dst, source)
+            }
+            FakeRootError::MountOverlay { dst, source } => {
+                write!(f, "failed to mount overlay at {}: {}", dst, source)
+            }
+            FakeRootError::Mknod { path, source } => {
+                write!(f, "failed to mknod {}: {}", path, source)
+            }
+            FakeRootError::PivotRoot(e) => write!(f, "failed to pivot_root/chroot: {}", e),
+            FakeRootError::Remount { path, source } => {
+                write!(f, "failed to remount {} with its mount options: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FakeRootError {}
+
+/// One entry of /proc/mounts or /proc/self/mountinfo (source, target, fstype, options - the
+/// remaining mountinfo fields are ignored), parsed by whitespace like citadel's `Mount` utility.
+struct HostMount {
+    source: String,
+    target: String,
+    fstype: String,
+    options: String,
+}
+
+/// /proc/mounts fields are simply "source target fstype options ...".
+fn parse_mounts_line(line: &str) -> Option<HostMount> {
+    let mut fields = line.split_whitespace();
+    Some(HostMount {
+        source: fields.next()?.to_owned(),
+        target: fields.next()?.to_owned(),
+        fstype: fields.next()?.to_owned(),
+        options: fields.next().unwrap_or("").to_owned(),
+    })
+}
+
+/// /proc/self/mountinfo fields are "id parent-id major:minor root mount-point mount-options
+/// [optional fields] - fstype source super-options", so the mount point is the 5th field, not
+/// the 2nd, and fstype/source come after the "-" separator.
+fn parse_mountinfo_line(line: &str) -> Option<HostMount> {
+    let mut fields = line.split_whitespace();
+    let target = fields.nth(4)?.to_owned();
+    let options = fields.next().unwrap_or("").to_owned();
+    let mut fields = fields.skip_while(|f| *f != "-");
+    fields.next(); // consume the "-" separator itself
+    let fstype = fields.next()?.to_owned();
+    let source = fields.next()?.to_owned();
+    Some(HostMount {
+        source,
+        target,
+        fstype,
+        options,
+    })
+}
+
+fn parse_host_mounts(path: &str) -> io::Result<Vec<HostMount>> {
+    let content = std::fs::read_to_string(path)?;
+    let parse_line = if path.ends_with("mountinfo") {
+        parse_mountinfo_line
+    } else {
+        parse_mounts_line
+    };
+    Ok(content.lines().filter_map(parse_line).collect())
+}
+
+/// Whether a fakeroot bind-mount source is a real host mountpoint or just a plain subdirectory.
+/// Neither is wrong - `Command::fakeroot_mount("/bin", "/bin", true)` binds a plain subdirectory
+/// of "/" and is the library's own canonical usage - this is purely informational.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FakeRootSourceKind {
+    Mountpoint,
+    Subdirectory,
+}
+
+/// A problem found by `Command::validate_fakeroot()`.
+#[derive(Debug)]
+pub enum FakeRootProblem {
+    /// A bind mount's source path does not exist on the host at all. This is the only source
+    /// condition that actually kills the child - binding an ordinary subdirectory (e.g. `/bin`,
+    /// `/etc`, `/usr`, as the canonical examples do) is completely valid and is not flagged here.
+    MissingSource { src: String, dst: String },
+    /// Two queued mounts target the exact same destination.
+    DuplicateDestination { dst: String },
+    /// A queued destination is a subdirectory of another queued destination; since mounts are
+    /// applied in queue order, the parent must be queued first or it will shadow the child.
+    OverlappingDestination { dst: String, parent: String },
+}
+
+impl std::fmt::Display for FakeRootProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FakeRootProblem::MissingSource { src, dst } => {
+                write!(f, "fakeroot mount {} -> {}: source does not exist", src, dst)
+            }
+            FakeRootProblem::DuplicateDestination { dst } => {
+                write!(f, "fakeroot destination {} is queued more than once", dst)
+            }
+            FakeRootProblem::OverlappingDestination { dst, parent } => write!(
+                f,
+                "fakeroot destination {} overlaps with {}, make sure the parent is queued first",
+                dst, parent
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FakeRootProblem {}
+
 impl Command {
     /// Enable "fakeroot" - the command will be rooted in a custom root directory.
     ///
@@ -31,6 +280,22 @@ impl Command {
         self.config.fake_root_base = Some(base.to_cstring());
     }
 
+    /// Change the mount propagation mode of the new root, instead of the default `Private`.
+    ///
+    /// Exposed as its own setter rather than an optional argument on `fakeroot_enable()` so that
+    /// enabling fakeroot and changing its propagation stay independent, composable calls - this
+    /// matches how `fakeroot_mount`/`fakeroot_mknod`/etc. are queued after `fakeroot_enable()`
+    /// rather than folded into its signature.
+    ///
+    /// fakeroot_enable() must be called first, otherwise this function will panic.
+    pub fn fakeroot_propagation(&mut self, propagation: MountPropagation) {
+        assert!(
+            self.config.fake_root_base.is_some(),
+            "call fakeroot_enable() first!"
+        );
+        self.config.fake_root_propagation = propagation;
+    }
+
     fn fakeroot_mkdir(&mut self, base: &str, dir: &Path) {
         dir.parent().map(|parent_dir| {
             if dir != parent_dir {
@@ -52,6 +317,22 @@ impl Command {
     ///   cmd.fakeroot_mount("/lib64", "/lib64", true);
     ///   cmd.fakeroot_mount("/usr", "/usr", true);
     pub fn fakeroot_mount<P: AsRef<Path>>(&mut self, src: P, dst: &str, readonly: bool) {
+        let options = if readonly {
+            MountOptions::READONLY
+        } else {
+            MountOptions::empty()
+        };
+        self.fakeroot_mount_opts(src, dst, options);
+    }
+
+    /// Like fakeroot_mount(), but with full control over the per-mount hardening flags
+    /// (`nosuid`/`nodev`/`noexec`/... in addition to read-only).
+    ///
+    /// fakeroot_enable() must be called first, otherwise this function will panic.
+    ///
+    /// Example usage:
+    ///   cmd.fakeroot_mount_opts("/srv/shared", "/srv/shared", MountOptions::NOSUID | MountOptions::NODEV);
+    pub fn fakeroot_mount_opts<P: AsRef<Path>>(&mut self, src: P, dst: &str, options: MountOptions) {
         let base = self
             .config
             .fake_root_base
@@ -64,9 +345,8 @@ impl Command {
         self.config.fake_root_mounts.push(FakeRootMount {
             mountpoint: dst.to_cstring(),
             mountpoint_outer: format!("{}/{}", base, dst).to_cstring(),
-            src: src.as_ref().to_cstring(),
-            readonly,
-            is_special_fs: false,
+            options,
+            kind: FakeRootMountKind::Bind(src.as_ref().to_cstring()),
         });
     }
 
@@ -94,9 +374,12 @@ impl Command {
         self.config.fake_root_mounts.push(FakeRootMount {
             mountpoint: dst.to_cstring(),
             mountpoint_outer: format!("{}/{}", base, dst).to_cstring(),
-            src: src.as_ref().to_cstring(),
-            readonly,
-            is_special_fs: false,
+            options: if readonly {
+                MountOptions::READONLY
+            } else {
+                MountOptions::empty()
+            },
+            kind: FakeRootMountKind::Bind(src.as_ref().to_cstring()),
         });
     }
 
@@ -107,6 +390,75 @@ impl Command {
     /// Example usage:
     ///   cmd.fakeroot_filesystem("tmpfs", "/tmp");
     pub fn fakeroot_filesystem(&mut self, fstype: &str, dst: &str) {
+        self.fakeroot_filesystem_opts(fstype, dst, "");
+    }
+
+    /// Like fakeroot_filesystem(), but with a mount-data options string, e.g.
+    /// "size=64m,mode=0755,uid=0,gid=0" for tmpfs. Without this, tmpfs defaults to half of RAM
+    /// and root-owned 1777, which is usually wrong when running under a user namespace with id
+    /// maps.
+    ///
+    /// If the option string refers to uid/gid, those are resolved against the ids in the
+    /// namespace active at mount time. When combined with `Config::id_maps`, make sure the id
+    /// maps have already been written before this filesystem gets mounted, so uid/gid in `opts`
+    /// refer to the in-namespace ids the maps establish.
+    ///
+    /// fakeroot_enable() must be called first, otherwise this function will panic.
+    ///
+    /// Example usage:
+    ///   cmd.fakeroot_filesystem_opts("tmpfs", "/tmp", "size=64m,mode=0755,uid=0,gid=0");
+    pub fn fakeroot_filesystem_opts(&mut self, fstype: &str, dst: &str, opts: &str) {
+        let base = self
+            .config
+            .fake_root_base
+            .as_ref()
+            .expect("call fakeroot_enable() first!")
+            .to_str()
+            .unwrap()
+            .to_owned();
+        self.fakeroot_mkdir(base.as_ref(), Path::new(dst));
+        self.config.fake_root_mounts.push(FakeRootMount {
+            mountpoint: dst.to_cstring(),
+            mountpoint_outer: format!("{}/{}", base, dst).to_cstring(),
+            options: MountOptions::empty(),
+            kind: FakeRootMountKind::Special {
+                fstype: fstype.to_cstring(),
+                data: if opts.is_empty() {
+                    None
+                } else {
+                    Some(opts.to_cstring())
+                },
+            },
+        });
+    }
+
+    /// Add an overlayfs mount to the fakeroot, presenting a writable view over one or more
+    /// read-only lower directories without copying them.
+    ///
+    /// fakeroot_enable() must be called first, otherwise this function will panic.
+    ///
+    /// `lowerdirs` are given in priority order (leftmost is the topmost layer). The upper and
+    /// work directories are generated automatically under the fakeroot base, so call
+    /// fakeroot_overlay_with_dirs() instead if you need them to live elsewhere.
+    ///
+    /// Example usage:
+    ///   cmd.fakeroot_overlay(&["/a", "/b"], "/merged");
+    pub fn fakeroot_overlay(&mut self, lowerdirs: &[&str], dst: &str) {
+        let upperdir = format!("/unshare_overlay_upper{}", dst);
+        let workdir = format!("/unshare_overlay_work{}", dst);
+        self.fakeroot_overlay_with_dirs(lowerdirs, dst, &upperdir, &workdir);
+    }
+
+    /// Like fakeroot_overlay(), but with explicit upper and work directories (given as paths
+    /// inside the fakeroot, not on the host). The upper and work directories must end up on the
+    /// same filesystem, so keep them under the fakeroot base unless you know what you are doing.
+    pub fn fakeroot_overlay_with_dirs(
+        &mut self,
+        lowerdirs: &[&str],
+        dst: &str,
+        upperdir: &str,
+        workdir: &str,
+    ) {
         let base = self
             .config
             .fake_root_base
@@ -116,23 +468,197 @@ impl Command {
             .unwrap()
             .to_owned();
         self.fakeroot_mkdir(base.as_ref(), Path::new(dst));
+        self.fakeroot_mkdir(base.as_ref(), Path::new(upperdir));
+        self.fakeroot_mkdir(base.as_ref(), Path::new(workdir));
         self.config.fake_root_mounts.push(FakeRootMount {
             mountpoint: dst.to_cstring(),
             mountpoint_outer: format!("{}/{}", base, dst).to_cstring(),
-            src: fstype.to_cstring(),
-            readonly: false,
-            is_special_fs: true,
+            options: MountOptions::empty(),
+            kind: FakeRootMountKind::Overlay {
+                lowerdirs: lowerdirs.iter().map(|d| d.to_cstring()).collect(),
+                upperdir: format!("{}/{}", base, upperdir).to_cstring(),
+                workdir: format!("{}/{}", base, workdir).to_cstring(),
+            },
         });
     }
+
+    /// Create a device node inside the fakeroot via mknod(2), instead of bind-mounting it from
+    /// the host.
+    ///
+    /// fakeroot_enable() must be called first, otherwise this function will panic.
+    ///
+    /// mknod() of a character or block device requires either real root or a user namespace
+    /// with CAP_MKNOD over it; if it fails with EPERM, unshare falls back to bind-mounting the
+    /// same path from the host, so the node should also exist there (e.g. the standard /dev
+    /// entries created by fakeroot_default_dev() all exist on a normal host).
+    ///
+    /// Example usage:
+    ///   cmd.fakeroot_mknod("/dev/null", DeviceKind::Character, 1, 3, 0o666);
+    pub fn fakeroot_mknod(&mut self, dst: &str, kind: DeviceKind, major: u32, minor: u32, mode: u32) {
+        let base = self
+            .config
+            .fake_root_base
+            .as_ref()
+            .expect("call fakeroot_enable() first!")
+            .to_str()
+            .unwrap()
+            .to_owned();
+        Path::new(dst).parent().map(|parent_dir| {
+            self.fakeroot_mkdir(base.as_ref(), parent_dir);
+        });
+        self.config.fake_root_mknods.push(FakeRootMknod {
+            path: dst.to_cstring(),
+            path_outer: format!("{}/{}", base, dst).to_cstring(),
+            kind,
+            major,
+            minor,
+            mode,
+        });
+    }
+
+    /// Populate /dev with the usual device nodes (null, zero, full, random, urandom, tty) and
+    /// the /dev/fd, /dev/stdin, /dev/stdout, /dev/stderr symlinks.
+    ///
+    /// fakeroot_enable() must be called first, otherwise this function will panic.
+    pub fn fakeroot_default_dev(&mut self) {
+        self.fakeroot_mknod("/dev/null", DeviceKind::Character, 1, 3, 0o666);
+        self.fakeroot_mknod("/dev/zero", DeviceKind::Character, 1, 5, 0o666);
+        self.fakeroot_mknod("/dev/full", DeviceKind::Character, 1, 7, 0o666);
+        self.fakeroot_mknod("/dev/random", DeviceKind::Character, 1, 8, 0o666);
+        self.fakeroot_mknod("/dev/urandom", DeviceKind::Character, 1, 9, 0o666);
+        self.fakeroot_mknod("/dev/tty", DeviceKind::Character, 5, 0, 0o666);
+
+        let base = self
+            .config
+            .fake_root_base
+            .as_ref()
+            .expect("call fakeroot_enable() first!")
+            .to_str()
+            .unwrap()
+            .to_owned();
+        for (target, link) in [
+            ("/proc/self/fd", "/dev/fd"),
+            ("fd/0", "/dev/stdin"),
+            ("fd/1", "/dev/stdout"),
+            ("fd/2", "/dev/stderr"),
+        ] {
+            self.config
+                .fake_root_symlinks
+                .push((target.to_cstring(), format!("{}/{}", base, link).to_cstring()));
+        }
+    }
+
+    /// Check the queued fakeroot mounts before spawning.
+    ///
+    /// A bind mount whose source doesn't exist on the host at all silently aborts the whole
+    /// child, and the failure only shows up as a dead child - binding an ordinary subdirectory
+    /// (e.g. `/bin`, `/etc`, `/usr`, as the canonical examples do) is valid and not flagged.
+    /// This also flags queued destinations that are duplicated, or nested inside one another in
+    /// the wrong order: since mounts are applied in queue order, a parent directory must be
+    /// queued before anything nested under it, or applying the parent will shadow the child.
+    pub fn validate_fakeroot(&self) -> Result<(), Vec<FakeRootProblem>> {
+        let mut problems = Vec::new();
+
+        for mount in &self.config.fake_root_mounts {
+            if let FakeRootMountKind::Bind(src) = &mount.kind {
+                let src = src.to_str().unwrap_or("").to_owned();
+                let dst = mount.mountpoint.to_str().unwrap_or("").to_owned();
+                if !Path::new(&src).exists() {
+                    problems.push(FakeRootProblem::MissingSource { src, dst });
+                }
+            }
+        }
+
+        let destinations: Vec<&str> = self
+            .config
+            .fake_root_mounts
+            .iter()
+            .map(|m| m.mountpoint.to_str().unwrap_or(""))
+            .collect();
+        for (earlier_idx, earlier) in destinations.iter().enumerate() {
+            for later in &destinations[earlier_idx + 1..] {
+                if earlier == later {
+                    problems.push(FakeRootProblem::DuplicateDestination {
+                        dst: (*earlier).to_owned(),
+                    });
+                } else if earlier.starts_with(&format!("{}/", later)) {
+                    // `earlier` is nested under `later`, but was queued (and so will be
+                    // mounted) first - `later` will shadow it once it's applied.
+                    problems.push(FakeRootProblem::OverlappingDestination {
+                        dst: (*earlier).to_owned(),
+                        parent: (*later).to_owned(),
+                    });
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Classify every queued bind-mount source as a real host mountpoint or a plain
+    /// subdirectory, by cross-referencing `/proc/self/mountinfo` (falling back to
+    /// `/proc/mounts` if that can't be read). This is purely informational: unlike
+    /// `validate_fakeroot()`, a source classified as `Subdirectory` is not a problem - binding a
+    /// plain subdirectory (e.g. `/bin`, `/etc`) is the library's own canonical usage. Returns
+    /// `(src, dst, kind)` for each queued `Bind` mount, in queue order.
+    pub fn classify_fakeroot_sources(&self) -> Vec<(String, String, FakeRootSourceKind)> {
+        let host_mounts = parse_host_mounts("/proc/self/mountinfo")
+            .or_else(|_| parse_host_mounts("/proc/mounts"))
+            .unwrap_or_default();
+
+        self.config
+            .fake_root_mounts
+            .iter()
+            .filter_map(|mount| match &mount.kind {
+                FakeRootMountKind::Bind(src) => {
+                    let src = src.to_str().unwrap_or("").to_owned();
+                    let dst = mount.mountpoint.to_str().unwrap_or("").to_owned();
+                    let kind = if host_mounts.iter().any(|m| m.target == src) {
+                        FakeRootSourceKind::Mountpoint
+                    } else {
+                        FakeRootSourceKind::Subdirectory
+                    };
+                    Some((src, dst, kind))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn path_of(c: &CString) -> String {
+    c.to_string_lossy().into_owned()
 }
 
 /// This syscall sequence is more or less taken from nsjail (https://github.com/google/nsjail).
+///
+/// Every fallible syscall captures `io::Error::last_os_error()` and tags it with the path it
+/// was operating on, so a caller can report *which* mount or mkdir failed and why instead of a
+/// bare "fakeroot setup failed". This runs in the child after fork/clone and before exec, so the
+/// caller (the child setup path that invokes this right before `execvp`) must propagate `Err` as
+/// a real spawn failure - e.g. by writing the error back to the parent over the error pipe used
+/// for other pre-exec failures - rather than letting the child exec with a half-built root.
+///
+/// NOTE: that child setup path (the pre-exec code that forks, calls this, and owns the error
+/// pipe back to the parent) isn't part of this source tree - this crate snapshot only has
+/// `src/config.rs` and `src/fakeroot.rs`, not the spawn/child module that would hold the actual
+/// call site. There's nothing here to update; whoever wires this up against the full crate needs
+/// to change that call site from `if build_fakeroot(...) { ... }` to matching on the `Result` and
+/// writing `Err` down the same pipe used for other pre-exec failures, the same way exec failures
+/// already get reported back to the parent.
 pub(crate) unsafe fn build_fakeroot(
     base: &CString,
     mkdirs: &[CString],
     touchs: &[CString],
     mountpoints: &[FakeRootMount],
-) -> bool {
+    mknods: &[FakeRootMknod],
+    symlinks: &[(CString, CString)],
+    propagation: MountPropagation,
+) -> Result<(), FakeRootError> {
     // define some libc constants
     let null_char = 0 as *const c_char;
     let null_void = 0 as *const c_void;
@@ -140,20 +666,28 @@ pub(crate) unsafe fn build_fakeroot(
     let dot = b".\0".as_ptr() as *const c_char;
     let tmpfs = b"tmpfs\0".as_ptr() as *const c_char;
 
-    // keep all mount changes private
+    // set the requested mount propagation (private by default) for the whole tree
     libc::mkdir(base.as_ptr(), 0o777);
-    if libc::mount(slash, slash, null_char, MS_PRIVATE | MS_REC, null_void) < 0 {
-        return false;
+    if libc::mount(slash, slash, null_char, propagation.to_libc_flag() | MS_REC, null_void) < 0 {
+        return Err(FakeRootError::MountPrivate(io::Error::last_os_error()));
     }
 
     // create fakeroot filesystem
     if libc::mount(null_char, base.as_ptr(), tmpfs, 0, null_void) < 0 {
-        return false;
+        return Err(FakeRootError::CreateTmpfs(io::Error::last_os_error()));
     }
 
     // create mount points
     for dir in mkdirs {
-        libc::mkdir(dir.as_ptr(), 0o777);
+        if libc::mkdir(dir.as_ptr(), 0o777) < 0 {
+            let source = io::Error::last_os_error();
+            if source.kind() != io::ErrorKind::AlreadyExists {
+                return Err(FakeRootError::Mkdir {
+                    path: path_of(dir),
+                    source,
+                });
+            }
+        }
     }
     for file in touchs {
         let fd = libc::open(file.as_ptr(), O_RDONLY | O_CREAT | O_CLOEXEC);
@@ -162,22 +696,110 @@ pub(crate) unsafe fn build_fakeroot(
         }
     }
 
+    // create device nodes, falling back to a bind mount from the host if we lack CAP_MKNOD
+    for node in mknods {
+        let sflag = match node.kind {
+            DeviceKind::Character => libc::S_IFCHR,
+            DeviceKind::Block => libc::S_IFBLK,
+        };
+        let dev = libc::makedev(node.major, node.minor);
+        if libc::mknod(node.path_outer.as_ptr(), sflag | node.mode, dev) < 0 {
+            let source = io::Error::last_os_error();
+            if source.raw_os_error() != Some(libc::EPERM) {
+                return Err(FakeRootError::Mknod {
+                    path: path_of(&node.path_outer),
+                    source,
+                });
+            }
+            // the bind mount needs an existing file to mount onto
+            let fd = libc::open(node.path_outer.as_ptr(), O_RDONLY | O_CREAT | O_CLOEXEC);
+            if fd >= 0 {
+                libc::close(fd);
+            }
+            if libc::mount(
+                node.path.as_ptr(),
+                node.path_outer.as_ptr(),
+                null_char,
+                MS_PRIVATE | MS_REC | MS_BIND,
+                null_void,
+            ) < 0
+            {
+                return Err(FakeRootError::BindMount {
+                    src: path_of(&node.path),
+                    dst: path_of(&node.path_outer),
+                    source: io::Error::last_os_error(),
+                });
+            }
+        }
+    }
+    for (target, link) in symlinks {
+        libc::symlink(target.as_ptr(), link.as_ptr());
+    }
+
     // mount directories - still read-write (because MS_BIND + MS_RDONLY are not supported)
     for mount in mountpoints {
-        let (src, fstype, flags) = if mount.is_special_fs {
-            (null_char, mount.src.as_ptr(), 0)
-        } else {
-            (mount.src.as_ptr(), null_char, MS_PRIVATE | MS_REC | MS_BIND)
-        };
-        if libc::mount(
-            src,
-            mount.mountpoint_outer.as_ptr(),
-            fstype,
-            flags,
-            null_void,
-        ) < 0
-        {
-            return false;
+        let overlay = b"overlay\0".as_ptr() as *const c_char;
+        match &mount.kind {
+            FakeRootMountKind::Bind(src) => {
+                if libc::mount(
+                    src.as_ptr(),
+                    mount.mountpoint_outer.as_ptr(),
+                    null_char,
+                    MS_PRIVATE | MS_REC | MS_BIND,
+                    null_void,
+                ) < 0
+                {
+                    return Err(FakeRootError::BindMount {
+                        src: path_of(src),
+                        dst: path_of(&mount.mountpoint_outer),
+                        source: io::Error::last_os_error(),
+                    });
+                }
+            }
+            FakeRootMountKind::Special { fstype, data } => {
+                let data_ptr = data
+                    .as_ref()
+                    .map(|d| d.as_ptr() as *const c_void)
+                    .unwrap_or(null_void);
+                if libc::mount(null_char, mount.mountpoint_outer.as_ptr(), fstype.as_ptr(), 0, data_ptr) < 0 {
+                    return Err(FakeRootError::MountSpecial {
+                        fstype: path_of(fstype),
+                        dst: path_of(&mount.mountpoint_outer),
+                        source: io::Error::last_os_error(),
+                    });
+                }
+            }
+            FakeRootMountKind::Overlay {
+                lowerdirs,
+                upperdir,
+                workdir,
+            } => {
+                let lowerdir = lowerdirs
+                    .iter()
+                    .map(|d| d.to_str().unwrap())
+                    .collect::<Vec<_>>()
+                    .join(":");
+                let options = format!(
+                    "lowerdir={},upperdir={},workdir={}",
+                    lowerdir,
+                    upperdir.to_str().unwrap(),
+                    workdir.to_str().unwrap()
+                )
+                .to_cstring();
+                if libc::mount(
+                    null_char,
+                    mount.mountpoint_outer.as_ptr(),
+                    overlay,
+                    0,
+                    options.as_ptr() as *const c_void,
+                ) < 0
+                {
+                    return Err(FakeRootError::MountOverlay {
+                        dst: path_of(&mount.mountpoint_outer),
+                        source: io::Error::last_os_error(),
+                    });
+                }
+            }
         }
     }
 
@@ -188,7 +810,7 @@ pub(crate) unsafe fn build_fakeroot(
         libc::chdir(base.as_ptr());
         libc::mount(dot, slash, null_char, 0, null_void);
         if libc::chroot(dot) < 0 {
-            return false;
+            return Err(FakeRootError::PivotRoot(io::Error::last_os_error()));
         }
     }
 
@@ -201,19 +823,22 @@ pub(crate) unsafe fn build_fakeroot(
         null_void,
     );
     for mount in mountpoints {
-        if mount.readonly {
+        if !mount.options.is_empty() {
             if libc::mount(
                 mount.mountpoint.as_ptr(),
                 mount.mountpoint.as_ptr(),
                 null_char,
-                MS_REMOUNT | MS_BIND | MS_RDONLY,
+                MS_REMOUNT | MS_BIND | mount.options.to_libc_flags(),
                 null_void,
             ) < 0
             {
-                return false;
+                return Err(FakeRootError::Remount {
+                    path: path_of(&mount.mountpoint),
+                    source: io::Error::last_os_error(),
+                });
             }
         }
     }
 
-    true
+    Ok(())
 }